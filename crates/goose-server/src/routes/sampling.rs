@@ -1,13 +1,15 @@
+use crate::sampling_audit::{AuditDecision, AuditFilter, AuditRecord};
 use crate::state::AppState;
 use axum::{
-    extract::{Path, State},
-    http::{self, StatusCode},
+    extract::{Path, Query, Request, State},
+    http::{self, HeaderMap, StatusCode},
+    middleware::{self, Next},
     response::IntoResponse,
     routing::{get, post},
     Json, Router,
 };
 use bytes::Bytes;
-use futures::Stream;
+use futures::{stream, Stream};
 use rmcp::model::CreateMessageRequestParam;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -19,12 +21,146 @@ use std::{
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::StreamExt;
 
+/// Standard SSE header used by clients to resume a stream after a reconnect.
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+
+/// Identity resolved from a sampling session token, threaded through request extensions by
+/// [`require_sampling_session`] so handlers can record who made a decision.
+#[derive(Debug, Clone)]
+struct SamplingIdentity(String);
+
+/// Requires a valid `Authorization: Bearer <session token>` issued by `/sampling/handshake`.
+/// Rejects with 401 otherwise.
+async fn require_sampling_session(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Result<axum::response::Response, StatusCode> {
+    let token = request
+        .headers()
+        .get(http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let identity = state
+        .sampling_auth
+        .authenticate(token)
+        .await
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    request.extensions_mut().insert(SamplingIdentity(identity));
+    Ok(next.run(request).await)
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct HandshakeRequest {
+    /// The shared secret configured on the server (env `GOOSE_SAMPLING_SHARED_SECRET`, or
+    /// generated at startup).
+    pub credential: String,
+    /// A human-readable identity for this client, recorded against the decisions it makes.
+    pub identity: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct HandshakeResponse {
+    pub session_token: String,
+}
+
+/// Incremental progress for an approved sampling request that's being generated, published as
+/// the provider streams tokens back and consumed via `GET /sampling/{request_id}/progress`.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SamplingProgressEvent {
+    /// Text generated so far (not just the latest chunk).
+    pub partial_text: String,
+    /// Cumulative output token count so far, when the provider reports it mid-stream.
+    pub cumulative_tokens: u32,
+    /// Set on the final event for this request.
+    pub done: bool,
+}
+
+fn sse_progress_event(event: &SamplingProgressEvent) -> Option<Bytes> {
+    match serde_json::to_string(event) {
+        Ok(json) => Some(Bytes::from(format!("data: {}\n\n", json))),
+        Err(e) => {
+            tracing::error!("Failed to serialize sampling progress event: {}", e);
+            None
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/sampling/handshake",
+    request_body = HandshakeRequest,
+    responses(
+        (status = 200, description = "Session token issued", body = HandshakeResponse),
+        (status = 401, description = "Invalid credential")
+    )
+)]
+async fn handshake(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<HandshakeRequest>,
+) -> Result<Json<HandshakeResponse>, StatusCode> {
+    let session_token = state
+        .sampling_auth
+        .handshake(&payload.credential, payload.identity)
+        .await
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    Ok(Json(HandshakeResponse { session_token }))
+}
+
+fn sse_event(request: &SamplingRequest) -> Option<Bytes> {
+    match serde_json::to_string(request) {
+        Ok(json) => Some(Bytes::from(format!("id: {}\ndata: {}\n\n", request.seq, json))),
+        Err(e) => {
+            tracing::error!("Failed to serialize sampling request: {}", e);
+            None
+        }
+    }
+}
+
+/// Out-of-band notifications about a sampling request's pending/approval state that aren't a
+/// new request itself, e.g. a request timing out before anyone decided it.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SamplingLifecycleEvent {
+    Expired { id: String, seq: u64 },
+}
+
+impl SamplingLifecycleEvent {
+    fn seq(&self) -> u64 {
+        match self {
+            SamplingLifecycleEvent::Expired { seq, .. } => *seq,
+        }
+    }
+}
+
+fn sse_lifecycle_event(event: &SamplingLifecycleEvent) -> Option<Bytes> {
+    match serde_json::to_string(event) {
+        Ok(json) => Some(Bytes::from(format!(
+            "id: {}\nevent: lifecycle\ndata: {}\n\n",
+            event.seq(),
+            json
+        ))),
+        Err(e) => {
+            tracing::error!("Failed to serialize sampling lifecycle event: {}", e);
+            None
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct SamplingRequest {
     pub id: String,
     pub extension_name: String,
     #[serde(flatten)]
     pub params: CreateMessageRequestParam,
+    /// Monotonic sequence number assigned when the request is broadcast, used as the SSE
+    /// `id:` field so reconnecting clients can resume via `Last-Event-ID`. `0` until then.
+    #[serde(default)]
+    pub seq: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
@@ -42,6 +178,7 @@ pub struct ApprovalResponse {
     path = "/sampling/pending",
     responses(
         (status = 200, description = "List of pending sampling requests", body = Vec<SamplingRequest>),
+        (status = 401, description = "Missing or invalid session token"),
         (status = 500, description = "Internal server error")
     )
 )]
@@ -65,10 +202,11 @@ async fn get_pending_requests(
 async fn approve_sampling_request(
     State(state): State<Arc<AppState>>,
     Path(request_id): Path<String>,
+    axum::extract::Extension(identity): axum::extract::Extension<SamplingIdentity>,
     Json(payload): Json<ApprovalRequest>,
 ) -> Result<Json<ApprovalResponse>, StatusCode> {
     state
-        .respond_to_sampling_request(&request_id, payload.approved)
+        .respond_to_sampling_request(&request_id, payload.approved, Some(identity.0))
         .await
         .map_err(|_| StatusCode::NOT_FOUND)?;
 
@@ -110,45 +248,173 @@ impl IntoResponse for SseResponse {
 )]
 async fn stream_sampling_requests(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
 ) -> Result<SseResponse, StatusCode> {
+    // A reconnecting client sends back the last `id:` it saw so we can replay anything it
+    // missed while disconnected (or that was dropped due to broadcast lag) before attaching
+    // it to the live stream.
+    let last_event_id: u64 = headers
+        .get(LAST_EVENT_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    // Subscribe to the live broadcast *before* snapshotting the replay buffer/pending map, so a
+    // request broadcast in between is caught by the live subscription rather than falling
+    // through the gap and being lost forever.
     let rx = state.subscribe_to_sampling_requests();
-    let stream = BroadcastStream::new(rx);
-
-    let mapped_stream = stream.filter_map(|result| {
-        match result {
-            Ok(request) => {
-                // Serialize the request to JSON
-                match serde_json::to_string(&request) {
-                    Ok(json) => {
-                        // Format as SSE event
-                        let sse_data = format!("data: {}\n\n", json);
-                        Some(Ok(Bytes::from(sse_data)))
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to serialize sampling request: {}", e);
-                        None
-                    }
-                }
-            }
-            Err(e) => {
-                tracing::error!("Broadcast stream error: {}", e);
-                None
-            }
+    let replay = state.sampling_requests_since(last_event_id).await;
+    let max_replayed_seq = replay.last().map(|req| req.seq).unwrap_or(last_event_id);
+    let replay_stream = stream::iter(replay.into_iter().filter_map(|req| sse_event(&req).map(Ok)));
+
+    let live_stream = BroadcastStream::new(rx).filter_map(move |result| match result {
+        // The replay snapshot may already include requests the live subscription also sees
+        // (it was taken after subscribing), so drop anything not newer than the replay's tail.
+        Ok(request) if request.seq <= max_replayed_seq => None,
+        Ok(request) => sse_event(&request).map(Ok),
+        Err(e) => {
+            tracing::error!("Broadcast stream error: {}", e);
+            None
+        }
+    });
+
+    let lifecycle_rx = state.subscribe_to_sampling_lifecycle();
+    let lifecycle_stream = BroadcastStream::new(lifecycle_rx).filter_map(|result| match result {
+        Ok(event) => sse_lifecycle_event(&event).map(Ok),
+        Err(e) => {
+            tracing::error!("Sampling lifecycle stream error: {}", e);
+            None
         }
     });
 
     Ok(SseResponse {
-        stream: Box::pin(mapped_stream),
+        stream: Box::pin(replay_stream.chain(live_stream.merge(lifecycle_stream))),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/sampling/{request_id}/progress",
+    responses(
+        (status = 200, description = "SSE stream of incremental generation progress", content_type = "text/event-stream"),
+        (status = 401, description = "Missing or invalid session token")
+    )
+)]
+async fn stream_sampling_progress(
+    State(state): State<Arc<AppState>>,
+    Path(request_id): Path<String>,
+) -> SseResponse {
+    let rx = state.subscribe_to_sampling_progress(&request_id).await;
+    let progress_stream = BroadcastStream::new(rx).filter_map(|result| match result {
+        Ok(event) => sse_progress_event(&event).map(Ok),
+        Err(e) => {
+            tracing::error!("Sampling progress stream error: {}", e);
+            None
+        }
+    });
+
+    SseResponse {
+        stream: Box::pin(progress_stream),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/sampling/policy/reload",
+    responses(
+        (status = 200, description = "Policy ruleset reloaded"),
+        (status = 401, description = "Missing or invalid session token"),
+        (status = 500, description = "Failed to reload the policy file")
+    )
+)]
+async fn reload_sampling_policy(State(state): State<Arc<AppState>>) -> StatusCode {
+    match state.reload_sampling_policy() {
+        Ok(()) => StatusCode::OK,
+        Err(e) => {
+            tracing::error!("Failed to reload sampling policy: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct HistoryQuery {
+    pub extension_name: Option<String>,
+    pub decision: Option<AuditDecision>,
+    #[serde(default)]
+    pub page: usize,
+    #[serde(default = "default_page_size")]
+    pub page_size: usize,
+}
+
+fn default_page_size() -> usize {
+    50
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct HistoryResponse {
+    pub records: Vec<AuditRecord>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+}
+
+#[utoipa::path(
+    get,
+    path = "/sampling/history",
+    params(HistoryQuery),
+    responses(
+        (status = 200, description = "Paginated sampling decision audit trail", body = HistoryResponse),
+        (status = 401, description = "Missing or invalid session token")
+    )
+)]
+async fn get_sampling_history(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HistoryQuery>,
+) -> Json<HistoryResponse> {
+    let page = query.page;
+    let page_size = query.page_size;
+    let (records, total) = state
+        .sampling_history(AuditFilter {
+            extension_name: query.extension_name,
+            decision: query.decision,
+            page,
+            page_size,
+        })
+        .await;
+
+    Json(HistoryResponse {
+        records,
+        total,
+        page,
+        page_size,
     })
 }
 
 pub fn routes(state: Arc<AppState>) -> Router {
-    Router::new()
+    // `/sampling/pending` returns full request bodies (prompt text, system prompt, etc.), so it
+    // sits behind the same handshake session-token middleware as approve/stream/policy-reload;
+    // only `/sampling/handshake` itself stays open so a fresh client can authenticate.
+    let authenticated = Router::new()
         .route("/sampling/pending", get(get_pending_requests))
         .route("/sampling/stream", get(stream_sampling_requests))
         .route(
             "/sampling/{request_id}/approve",
             post(approve_sampling_request),
         )
+        .route("/sampling/policy/reload", post(reload_sampling_policy))
+        .route("/sampling/history", get(get_sampling_history))
+        .route(
+            "/sampling/{request_id}/progress",
+            get(stream_sampling_progress),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_sampling_session,
+        ));
+
+    Router::new()
+        .route("/sampling/handshake", post(handshake))
+        .merge(authenticated)
         .with_state(state)
 }
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, Mutex};
+
+/// How a sampling request was ultimately resolved, for the audit trail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditDecision {
+    Approved,
+    Rejected,
+    Expired,
+    AutoApproved,
+    AutoRejected,
+}
+
+/// One row of the sampling audit trail. Fields are filled in incrementally as a request moves
+/// through creation, decision, and (if approved) provider completion; each stage persists the
+/// merged record so `GET /sampling/history` always reflects the latest known state.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AuditRecord {
+    pub id: String,
+    pub extension_name: String,
+    pub prompt_excerpt: String,
+    pub created_at_ms: u64,
+    pub model: Option<String>,
+    pub input_tokens: Option<i64>,
+    pub output_tokens: Option<i64>,
+    pub decision: Option<AuditDecision>,
+    pub decided_by: Option<String>,
+    pub matched_rule: Option<String>,
+    pub decided_at_ms: Option<u64>,
+}
+
+/// Longest prompt excerpt kept in the audit trail; sampling prompts can be large and this log
+/// is meant for "who approved what", not full transcript storage.
+const PROMPT_EXCERPT_LEN: usize = 500;
+
+pub fn truncate_for_audit(text: &str) -> String {
+    if text.chars().count() <= PROMPT_EXCERPT_LEN {
+        text.to_string()
+    } else {
+        let truncated: String = text.chars().take(PROMPT_EXCERPT_LEN).collect();
+        format!("{}…", truncated)
+    }
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Optional filters for `GET /sampling/history`.
+#[derive(Debug, Default, Clone)]
+pub struct AuditFilter {
+    pub extension_name: Option<String>,
+    pub decision: Option<AuditDecision>,
+    pub page: usize,
+    pub page_size: usize,
+}
+
+/// Append-only JSONL audit log of sampling decisions, with an in-memory cache (hydrated from
+/// disk on startup) backing the paginated history endpoint and a background task doing the
+/// actual file writes so callers never block on disk I/O.
+pub struct AuditLogger {
+    cache: Arc<Mutex<HashMap<String, AuditRecord>>>,
+    tx: mpsc::UnboundedSender<AuditRecord>,
+}
+
+impl AuditLogger {
+    pub async fn new() -> Self {
+        let log_path = std::env::var("GOOSE_SAMPLING_AUDIT_LOG_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("sampling_audit.jsonl"));
+
+        let cache = Arc::new(Mutex::new(Self::load(&log_path).await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to load existing sampling audit log: {}", e);
+            HashMap::new()
+        })));
+
+        let (tx, rx) = mpsc::unbounded_channel::<AuditRecord>();
+        Self::spawn_writer(log_path, rx);
+
+        Self { cache, tx }
+    }
+
+    async fn load(path: &Path) -> anyhow::Result<HashMap<String, AuditRecord>> {
+        let contents = match tokio::fs::read_to_string(path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut records = HashMap::new();
+        for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+            match serde_json::from_str::<AuditRecord>(line) {
+                Ok(record) => {
+                    records.insert(record.id.clone(), record);
+                }
+                Err(e) => tracing::warn!("Skipping unreadable sampling audit line: {}", e),
+            }
+        }
+        Ok(records)
+    }
+
+    fn spawn_writer(log_path: PathBuf, mut rx: mpsc::UnboundedReceiver<AuditRecord>) {
+        tokio::spawn(async move {
+            while let Some(record) = rx.recv().await {
+                if let Err(e) = Self::append(&log_path, &record).await {
+                    tracing::error!("Failed to append sampling audit record: {}", e);
+                }
+            }
+        });
+    }
+
+    async fn append(path: &Path, record: &AuditRecord) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        Ok(())
+    }
+
+    async fn upsert(&self, id: &str, apply: impl FnOnce(&mut AuditRecord)) {
+        let mut cache = self.cache.lock().await;
+        let entry = cache.entry(id.to_string()).or_insert_with(|| AuditRecord {
+            id: id.to_string(),
+            extension_name: String::new(),
+            prompt_excerpt: String::new(),
+            created_at_ms: now_ms(),
+            model: None,
+            input_tokens: None,
+            output_tokens: None,
+            decision: None,
+            decided_by: None,
+            matched_rule: None,
+            decided_at_ms: None,
+        });
+        apply(entry);
+        let _ = self.tx.send(entry.clone());
+    }
+
+    /// Record that a sampling request was created, before any decision has been made.
+    pub async fn record_created(&self, id: &str, extension_name: &str, prompt_excerpt: String) {
+        self.upsert(id, |record| {
+            record.extension_name = extension_name.to_string();
+            record.prompt_excerpt = prompt_excerpt;
+        })
+        .await;
+    }
+
+    /// Record the final approve/reject/expire decision for a request.
+    pub async fn record_decision(
+        &self,
+        id: &str,
+        decision: AuditDecision,
+        decided_by: Option<String>,
+        matched_rule: Option<String>,
+    ) {
+        self.upsert(id, |record| {
+            record.decision = Some(decision);
+            record.decided_by = decided_by;
+            record.matched_rule = matched_rule;
+            record.decided_at_ms = Some(now_ms());
+        })
+        .await;
+    }
+
+    /// Record the provider's completion usage once generation finishes.
+    pub async fn record_completion(
+        &self,
+        id: &str,
+        model: String,
+        input_tokens: Option<i64>,
+        output_tokens: Option<i64>,
+    ) {
+        self.upsert(id, |record| {
+            record.model = Some(model);
+            record.input_tokens = input_tokens;
+            record.output_tokens = output_tokens;
+        })
+        .await;
+    }
+
+    /// Paginated, filtered view of the audit trail, most recent first.
+    pub async fn history(&self, filter: AuditFilter) -> (Vec<AuditRecord>, usize) {
+        let cache = self.cache.lock().await;
+        let mut matching: Vec<AuditRecord> = cache
+            .values()
+            .filter(|r| {
+                filter
+                    .extension_name
+                    .as_ref()
+                    .map(|ext| &r.extension_name == ext)
+                    .unwrap_or(true)
+                    && filter
+                        .decision
+                        .map(|d| r.decision == Some(d))
+                        .unwrap_or(true)
+            })
+            .cloned()
+            .collect();
+        drop(cache);
+
+        matching.sort_by(|a, b| b.created_at_ms.cmp(&a.created_at_ms));
+        let total = matching.len();
+
+        let page_size = filter.page_size.max(1);
+        let start = filter.page.saturating_mul(page_size).min(total);
+        let end = (start + page_size).min(total);
+
+        (matching[start..end].to_vec(), total)
+    }
+}
@@ -0,0 +1,83 @@
+use rand::RngCore;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+use tokio::sync::Mutex;
+
+/// How long a session token issued by the handshake endpoint remains valid.
+const SESSION_TOKEN_TTL: Duration = Duration::from_secs(60 * 60);
+
+struct SessionEntry {
+    identity: String,
+    expires_at: Instant,
+}
+
+/// Guards the sampling approval endpoints behind a shared-secret handshake.
+///
+/// A client exchanges the shared secret (generated on startup, or provided via
+/// `GOOSE_SAMPLING_SHARED_SECRET`) for a short-lived session token at `/sampling/handshake`,
+/// then presents that token as a bearer credential on subsequent sampling requests.
+pub struct SamplingAuth {
+    shared_secret: String,
+    sessions: Mutex<HashMap<String, SessionEntry>>,
+}
+
+impl SamplingAuth {
+    pub fn new() -> Self {
+        let shared_secret = std::env::var("GOOSE_SAMPLING_SHARED_SECRET").unwrap_or_else(|_| {
+            let mut bytes = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut bytes);
+            bytes.iter().map(|b| format!("{:02x}", b)).collect()
+        });
+
+        Self {
+            shared_secret,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Exchange the shared secret for a session token bound to `identity`. Returns `None` if
+    /// `credential` doesn't match the configured shared secret.
+    pub async fn handshake(&self, credential: &str, identity: String) -> Option<String> {
+        // Constant-time comparison: this is the one secret gating the whole approval/stream
+        // surface, and a `!=` here would let a timing attack recover it byte by byte.
+        let matches = credential.len() == self.shared_secret.len()
+            && credential
+                .as_bytes()
+                .ct_eq(self.shared_secret.as_bytes())
+                .into();
+        if !matches {
+            return None;
+        }
+
+        let token = uuid::Uuid::new_v4().to_string();
+        self.sessions.lock().await.insert(
+            token.clone(),
+            SessionEntry {
+                identity,
+                expires_at: Instant::now() + SESSION_TOKEN_TTL,
+            },
+        );
+
+        Some(token)
+    }
+
+    /// Resolve a bearer token to the identity it was issued to, evicting it if expired.
+    pub async fn authenticate(&self, token: &str) -> Option<String> {
+        let mut sessions = self.sessions.lock().await;
+        match sessions.get(token) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.identity.clone()),
+            Some(_) => {
+                sessions.remove(token);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+impl Default for SamplingAuth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,7 +1,9 @@
-use crate::routes::sampling::SamplingRequest;
+use crate::routes::sampling::{SamplingProgressEvent, SamplingRequest};
 use crate::state::AppState;
+use futures::StreamExt;
 use goose::agents::mcp_client::SamplingHandler;
-use goose::providers::base::Provider;
+use goose::conversation::message::{Message, MessageContent};
+use goose::providers::base::{Provider, ProviderUsage};
 use rmcp::model::{Content, CreateMessageRequestParam, CreateMessageResult, Role, SamplingMessage};
 use rmcp::ServiceError;
 use std::sync::Arc;
@@ -27,6 +29,72 @@ impl ServerSamplingHandler {
             app_state,
         }
     }
+
+    /// Drive the provider's streaming interface, publishing a `SamplingProgressEvent` for each
+    /// chunk on `request_id`'s progress channel, and assemble the final message/usage once the
+    /// stream ends so the caller can return it through the MCP oneshot exactly as a non-streamed
+    /// completion would.
+    async fn complete_streaming(
+        &self,
+        request_id: &str,
+        provider: &dyn Provider,
+        system_prompt: &str,
+        messages: &[Message],
+    ) -> Result<(Message, ProviderUsage), ServiceError> {
+        let mut stream = provider
+            .stream(system_prompt, messages, &[])
+            .await
+            .map_err(|_e| ServiceError::UnexpectedResponse)?;
+
+        let mut partial_text = String::new();
+        let mut cumulative_tokens: u32 = 0;
+        let mut last_usage: Option<ProviderUsage> = None;
+
+        while let Some(chunk) = stream.next().await {
+            let (message, usage) = chunk.map_err(|_e| ServiceError::UnexpectedResponse)?;
+
+            if let Some(message) = message {
+                for content in &message.content {
+                    if let MessageContent::Text(text) = content {
+                        partial_text.push_str(&text.text);
+                    }
+                }
+            }
+            if let Some(usage) = usage {
+                cumulative_tokens = usage
+                    .usage
+                    .output_tokens
+                    .map(|t| t as u32)
+                    .unwrap_or(cumulative_tokens);
+                last_usage = Some(usage);
+            }
+
+            self.app_state
+                .publish_sampling_progress(
+                    request_id,
+                    SamplingProgressEvent {
+                        partial_text: partial_text.clone(),
+                        cumulative_tokens,
+                        done: false,
+                    },
+                )
+                .await;
+        }
+
+        self.app_state
+            .publish_sampling_progress(
+                request_id,
+                SamplingProgressEvent {
+                    partial_text: partial_text.clone(),
+                    cumulative_tokens,
+                    done: true,
+                },
+            )
+            .await;
+
+        let usage = last_usage.ok_or(ServiceError::UnexpectedResponse)?;
+        Ok((Message::assistant().with_text(&partial_text), usage))
+    }
 }
 
 #[async_trait::async_trait]
@@ -43,6 +111,7 @@ impl SamplingHandler for ServerSamplingHandler {
             id: request_id.clone(),
             extension_name: self.extension_name.clone(),
             params: params.clone(),
+            seq: 0,
         };
 
         // Add the sampling request and get a receiver for the approval response
@@ -52,22 +121,33 @@ impl SamplingHandler for ServerSamplingHandler {
             .await
             .map_err(|_| ServiceError::UnexpectedResponse)?;
 
-        // Wait for human approval or rejection
-        let approved = approval_rx
+        // Wait for human approval or rejection, falling back to the configured default once
+        // the per-request timeout elapses.
+        let approved = self
+            .app_state
+            .await_sampling_decision(&request_id, approval_rx)
             .await
             .map_err(|_| ServiceError::UnexpectedResponse)?;
 
         if !approved {
-            // User rejected the sampling request
+            // User rejected (or the request expired without streaming); nobody will ever
+            // publish a `done` event for it, so close out any progress subscriber ourselves.
+            self.app_state.close_sampling_progress(&request_id).await;
             return Err(ServiceError::Cancelled { reason: Some("User rejected sampling request".to_string()) });
         }
 
-        // User approved - proceed with the sampling request using the provider
+        // User approved - proceed with the sampling request using the provider. From here on,
+        // any early return must close out the progress channel itself: only complete_streaming's
+        // own `done` event does that on the success path.
         let provider_lock = self.provider.lock().await;
-        let provider = provider_lock
-            .as_ref()
-            .ok_or_else(|| ServiceError::UnexpectedResponse)?
-            .clone();
+        let provider = match provider_lock.as_ref() {
+            Some(provider) => provider.clone(),
+            None => {
+                drop(provider_lock);
+                self.app_state.close_sampling_progress(&request_id).await;
+                return Err(ServiceError::UnexpectedResponse);
+            }
+        };
         drop(provider_lock);
 
         // Convert SamplingMessage to Message for the provider
@@ -96,11 +176,29 @@ impl SamplingHandler for ServerSamplingHandler {
             .as_deref()
             .unwrap_or("You are a helpful assistant");
 
-        // Call the provider's complete method
-        let (response, usage) = provider
-            .complete(system_prompt, &messages, &[])
-            .await
-            .map_err(|_e| ServiceError::UnexpectedResponse)?;
+        // Drive token-by-token generation and publish incremental progress when the provider
+        // supports it; otherwise fall back to a single blocking call.
+        let (response, usage) = if provider.supports_streaming() {
+            // complete_streaming cleans up the progress channel itself via its `done` event
+            // once it reaches the end of the stream, but not if it returns early (stream init
+            // failure or a chunk error) - close it ourselves then.
+            let result = self
+                .complete_streaming(&request_id, provider.as_ref(), system_prompt, &messages)
+                .await;
+            if result.is_err() {
+                self.app_state.close_sampling_progress(&request_id).await;
+            }
+            result?
+        } else {
+            // This provider never calls publish_sampling_progress, so there's no `done` event
+            // to close out a progress subscriber - do it ourselves once generation finishes.
+            let result = provider
+                .complete(system_prompt, &messages, &[])
+                .await
+                .map_err(|_e| ServiceError::UnexpectedResponse);
+            self.app_state.close_sampling_progress(&request_id).await;
+            result?
+        };
 
         // Extract the response content - convert MessageContent to Content
         let response_content = if let Some(content) = response.content.first() {
@@ -117,6 +215,16 @@ impl SamplingHandler for ServerSamplingHandler {
             Content::text("")
         };
 
+        // Record the provider's token usage against the audit trail now that generation finished.
+        self.app_state
+            .record_sampling_completion(
+                &request_id,
+                usage.model.clone(),
+                usage.usage.input_tokens.map(i64::from),
+                usage.usage.output_tokens.map(i64::from),
+            )
+            .await;
+
         // Create the result
         let result = CreateMessageResult {
             model: usage.model,
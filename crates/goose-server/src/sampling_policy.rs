@@ -0,0 +1,187 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::routes::sampling::SamplingRequest;
+
+/// The outcome a matching [`PolicyRule`] resolves a sampling request to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyDecision {
+    AutoApprove,
+    AutoReject,
+    RequireHuman,
+}
+
+/// A single ordered rule matched against an incoming [`SamplingRequest`]. `None` fields match
+/// anything; all present fields must match for the rule to apply.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PolicyRule {
+    /// Rule name surfaced on the decision so operators can see which rule fired.
+    pub name: String,
+    pub extension_name: Option<String>,
+    pub model: Option<String>,
+    pub max_tokens: Option<u32>,
+    #[serde(default, with = "serde_regex_opt")]
+    pub message_pattern: Option<Regex>,
+    #[serde(default, with = "serde_regex_opt")]
+    pub system_prompt_pattern: Option<Regex>,
+    pub decision: PolicyDecision,
+}
+
+impl PolicyRule {
+    fn matches(&self, request: &SamplingRequest) -> bool {
+        if let Some(extension_name) = &self.extension_name {
+            if extension_name != &request.extension_name {
+                return false;
+            }
+        }
+        if let Some(model) = &self.model {
+            let hint_matches = request
+                .params
+                .model_preferences
+                .as_ref()
+                .and_then(|prefs| prefs.hints.as_ref())
+                .map(|hints| hints.iter().any(|h| h.name.as_deref() == Some(model.as_str())))
+                .unwrap_or(false);
+            if !hint_matches {
+                return false;
+            }
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            if request.params.max_tokens as u64 > max_tokens as u64 {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.message_pattern {
+            let matches_any_message = request.params.messages.iter().any(|m| {
+                m.content
+                    .as_text()
+                    .map(|t| pattern.is_match(&t.text))
+                    .unwrap_or(false)
+            });
+            if !matches_any_message {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.system_prompt_pattern {
+            let matches_system = request
+                .params
+                .system_prompt
+                .as_deref()
+                .map(|s| pattern.is_match(s))
+                .unwrap_or(false);
+            if !matches_system {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Outcome of evaluating the ruleset against a request.
+pub enum PolicyOutcome {
+    /// A rule matched and decided the request without any human in the loop.
+    Decided {
+        approved: bool,
+        matched_rule: String,
+    },
+    /// No rule matched decisively; fall back to the normal human-approval flow.
+    RequireHuman,
+}
+
+/// Ordered, hot-reloadable set of rules consulted before a sampling request is ever broadcast
+/// for human approval, so trusted extensions can be whitelisted (or known-bad ones rejected)
+/// without an operator in the loop.
+pub struct SamplingPolicy {
+    config_path: Option<PathBuf>,
+    rules: RwLock<Vec<PolicyRule>>,
+}
+
+impl SamplingPolicy {
+    /// Loads the ruleset from `GOOSE_SAMPLING_POLICY_FILE` if set, otherwise starts empty
+    /// (every request requires a human decision).
+    pub fn new() -> Self {
+        let config_path = std::env::var("GOOSE_SAMPLING_POLICY_FILE")
+            .ok()
+            .map(PathBuf::from);
+
+        let rules = config_path
+            .as_deref()
+            .map(Self::load_rules)
+            .transpose()
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to load sampling policy file: {}", e);
+                None
+            })
+            .unwrap_or_default();
+
+        Self {
+            config_path,
+            rules: RwLock::new(rules),
+        }
+    }
+
+    fn load_rules(path: &Path) -> anyhow::Result<Vec<PolicyRule>> {
+        let contents = std::fs::read_to_string(path)?;
+        let rules: Vec<PolicyRule> = serde_json::from_str(&contents)?;
+        Ok(rules)
+    }
+
+    /// Re-read the ruleset from disk, leaving the existing rules in place if reloading fails.
+    pub fn reload(&self) -> anyhow::Result<()> {
+        let Some(config_path) = &self.config_path else {
+            return Ok(());
+        };
+
+        let rules = Self::load_rules(config_path)?;
+        *self.rules.write().unwrap() = rules;
+        Ok(())
+    }
+
+    /// Evaluate the ordered ruleset against `request`. The first matching rule decides; a
+    /// matching `RequireHuman` rule stops evaluation just like the other decisions.
+    pub fn evaluate(&self, request: &SamplingRequest) -> PolicyOutcome {
+        let rules = self.rules.read().unwrap();
+        for rule in rules.iter() {
+            if !rule.matches(request) {
+                continue;
+            }
+            return match rule.decision {
+                PolicyDecision::AutoApprove => PolicyOutcome::Decided {
+                    approved: true,
+                    matched_rule: rule.name.clone(),
+                },
+                PolicyDecision::AutoReject => PolicyOutcome::Decided {
+                    approved: false,
+                    matched_rule: rule.name.clone(),
+                },
+                PolicyDecision::RequireHuman => PolicyOutcome::RequireHuman,
+            };
+        }
+        PolicyOutcome::RequireHuman
+    }
+}
+
+impl Default for SamplingPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `serde_regex`-style helper so `PolicyRule` can deserialize `Option<Regex>` fields straight
+/// from a plain string in the policy config file.
+mod serde_regex_opt {
+    use regex::Regex;
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Regex>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw: Option<String> = Option::deserialize(deserializer)?;
+        raw.map(|s| Regex::new(&s).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
@@ -1,16 +1,69 @@
 use axum::http::StatusCode;
 use goose::execution::manager::AgentManager;
 use goose::scheduler_trait::SchedulerTrait;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{oneshot, Mutex};
+use tokio::time::interval;
 
-use crate::routes::sampling::SamplingRequest;
+use crate::routes::sampling::{SamplingLifecycleEvent, SamplingProgressEvent, SamplingRequest};
+use crate::sampling_audit::{self, AuditDecision, AuditLogger};
+use crate::sampling_auth::SamplingAuth;
+use crate::sampling_policy::{PolicyOutcome, SamplingPolicy};
+
+/// Best-effort text excerpt of a sampling request used for the audit trail, preferring the last
+/// user message and falling back to the system prompt.
+fn prompt_excerpt(request: &SamplingRequest) -> String {
+    request
+        .params
+        .messages
+        .iter()
+        .rev()
+        .filter(|m| matches!(m.role, rmcp::model::Role::User))
+        .find_map(|m| m.content.as_text().map(|t| t.text.clone()))
+        .or_else(|| request.params.system_prompt.clone())
+        .unwrap_or_default()
+}
 
 type PendingSamplingRequests =
-    Arc<Mutex<HashMap<String, (SamplingRequest, oneshot::Sender<bool>)>>>;
+    Arc<Mutex<HashMap<String, (SamplingRequest, oneshot::Sender<bool>, Instant)>>>;
+
+/// How many recently broadcast sampling requests we keep around so that a
+/// reconnecting SSE client can be caught up via `Last-Event-ID`.
+const SAMPLING_REPLAY_BUFFER_SIZE: usize = 256;
+
+/// How often the reaper sweeps `pending_sampling_requests` for abandoned entries (those whose
+/// waiter has gone away).
+const SAMPLING_REAPER_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Configuration for how long a sampling request waits for a human decision before it is
+/// resolved automatically, and what it resolves to.
+#[derive(Debug, Clone, Copy)]
+pub struct SamplingConfig {
+    /// How long a request may sit in `pending_sampling_requests` before
+    /// `await_sampling_decision` resolves it with `default_approved`.
+    pub timeout: Duration,
+    /// The decision applied when a request times out. Defaults to `false` (reject) so a
+    /// forgotten approval fails closed rather than silently running an unapproved sampling call.
+    pub default_approved: bool,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        let timeout_secs = std::env::var("GOOSE_SAMPLING_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300);
+
+        Self {
+            timeout: Duration::from_secs(timeout_secs),
+            default_approved: false,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct AppState {
@@ -23,20 +76,121 @@ pub struct AppState {
     pub(crate) pending_sampling_requests: PendingSamplingRequests,
     /// Broadcast channel for notifying about new sampling requests
     pub(crate) sampling_request_tx: Arc<tokio::sync::broadcast::Sender<SamplingRequest>>,
+    /// Monotonic sequence counter used to give every sampling request an `id:` for SSE replay
+    sampling_seq: Arc<AtomicU64>,
+    /// Ring buffer of the most recently broadcast sampling requests, used to replay events a
+    /// reconnecting client missed (keyed off the `Last-Event-ID` header)
+    sampling_replay_buffer: Arc<Mutex<VecDeque<SamplingRequest>>>,
+    /// Broadcast channel for sampling lifecycle notifications (e.g. expiry) other than new requests
+    pub(crate) sampling_lifecycle_tx: Arc<tokio::sync::broadcast::Sender<SamplingLifecycleEvent>>,
+    sampling_config: SamplingConfig,
+    /// Shared-secret handshake / session token auth for the sampling approval endpoints
+    pub(crate) sampling_auth: Arc<SamplingAuth>,
+    /// Ordered ruleset that can auto-approve/reject a sampling request before a human ever sees it
+    pub(crate) sampling_policy: Arc<SamplingPolicy>,
+    /// Append-only audit trail of every sampling request's lifecycle and decision
+    pub(crate) sampling_audit: Arc<AuditLogger>,
+    /// Per-request broadcast channels of incremental generation progress, created on demand and
+    /// torn down once the request's completion (or failure) is recorded
+    sampling_progress: Arc<Mutex<HashMap<String, tokio::sync::broadcast::Sender<SamplingProgressEvent>>>>,
 }
 
 impl AppState {
     pub async fn new() -> anyhow::Result<Arc<AppState>> {
+        Self::new_with_sampling_config(SamplingConfig::default()).await
+    }
+
+    pub async fn new_with_sampling_config(
+        sampling_config: SamplingConfig,
+    ) -> anyhow::Result<Arc<AppState>> {
         let agent_manager = AgentManager::instance().await?;
         let (sampling_tx, _) = tokio::sync::broadcast::channel(100);
-        Ok(Arc::new(Self {
+        let (sampling_lifecycle_tx, _) = tokio::sync::broadcast::channel(100);
+        let state = Arc::new(Self {
             agent_manager,
             recipe_file_hash_map: Arc::new(Mutex::new(HashMap::new())),
             session_counter: Arc::new(AtomicUsize::new(0)),
             recipe_session_tracker: Arc::new(Mutex::new(HashSet::new())),
             pending_sampling_requests: Arc::new(Mutex::new(HashMap::new())),
             sampling_request_tx: Arc::new(sampling_tx),
-        }))
+            sampling_seq: Arc::new(AtomicU64::new(0)),
+            sampling_replay_buffer: Arc::new(Mutex::new(VecDeque::with_capacity(
+                SAMPLING_REPLAY_BUFFER_SIZE,
+            ))),
+            sampling_lifecycle_tx: Arc::new(sampling_lifecycle_tx),
+            sampling_config,
+            sampling_auth: Arc::new(SamplingAuth::new()),
+            sampling_policy: Arc::new(SamplingPolicy::new()),
+            sampling_audit: Arc::new(AuditLogger::new().await),
+            sampling_progress: Arc::new(Mutex::new(HashMap::new())),
+        });
+
+        state.clone().spawn_sampling_reaper();
+
+        Ok(state)
+    }
+
+    /// Backstop sweep of `pending_sampling_requests` for entries nobody is waiting on anymore.
+    /// Under normal operation `await_sampling_decision` is the one that resolves a timed-out
+    /// request (see `expire_sampling_request`) the moment its own timer fires, so this sees
+    /// nothing; it only catches genuinely orphaned entries (e.g. the waiting task was cancelled
+    /// before it could clean up), which show up as a closed `oneshot::Sender`.
+    fn spawn_sampling_reaper(self: Arc<Self>) {
+        let mut ticker = interval(SAMPLING_REAPER_INTERVAL);
+        tokio::spawn(async move {
+            loop {
+                ticker.tick().await;
+
+                let orphaned_ids: Vec<String> = {
+                    let pending = self.pending_sampling_requests.lock().await;
+                    pending
+                        .iter()
+                        .filter(|(_, (_, tx, _))| tx.is_closed())
+                        .map(|(id, _)| id.clone())
+                        .collect()
+                };
+
+                for id in orphaned_ids {
+                    self.expire_sampling_request(&id).await;
+                }
+            }
+        });
+    }
+
+    /// Authoritatively resolve a pending sampling request as expired: remove it from the
+    /// pending map, record the `Expired` audit decision, and emit the SSE lifecycle event,
+    /// all before anyone else can act on it. Idempotent - if the request was already resolved
+    /// (approved/rejected/expired) by the time this runs, it's a no-op.
+    async fn expire_sampling_request(&self, request_id: &str) -> bool {
+        let removed = self
+            .pending_sampling_requests
+            .lock()
+            .await
+            .remove(request_id);
+
+        if let Some((request, _tx, _)) = removed {
+            tracing::warn!(
+                "Sampling request {} timed out waiting for a decision; resolving as {}",
+                request.id,
+                if self.sampling_config.default_approved {
+                    "approved"
+                } else {
+                    "rejected"
+                }
+            );
+            self.sampling_audit
+                .record_decision(&request.id, AuditDecision::Expired, None, None)
+                .await;
+            let _ = self
+                .sampling_lifecycle_tx
+                .send(SamplingLifecycleEvent::Expired {
+                    id: request.id.clone(),
+                    seq: request.seq,
+                });
+            self.close_sampling_progress(&request.id).await;
+        }
+
+        self.sampling_config.default_approved
     }
 
     pub async fn scheduler(&self) -> Result<Arc<dyn SchedulerTrait>, anyhow::Error> {
@@ -75,20 +229,17 @@ impl AppState {
                     use crate::routes::sampling::SamplingRequest;
 
                     // Create SamplingRequest directly from params
+                    let request_id = uuid::Uuid::new_v4().to_string();
                     let request = SamplingRequest {
-                        id: uuid::Uuid::new_v4().to_string(),
+                        id: request_id.clone(),
                         extension_name,
                         params,
+                        seq: 0,
                     };
 
-                    // Add the sampling request and wait for approval
+                    // Add the sampling request and wait for approval (or the configured timeout)
                     let rx = state.add_sampling_request(request).await?;
-
-                    // Wait for the approval response
-                    match rx.await {
-                        Ok(approved) => Ok(approved),
-                        Err(_) => Err(anyhow::anyhow!("Approval channel closed")),
-                    }
+                    state.await_sampling_decision(&request_id, rx).await
                 })
             },
         );
@@ -112,19 +263,64 @@ impl AppState {
         })
     }
 
-    /// Add a new sampling request and return a receiver for the approval response
+    /// Add a new sampling request and return a receiver for the approval response. If the
+    /// policy engine resolves the request non-interactively, the request never enters the
+    /// pending map or SSE stream at all and the receiver already carries the policy's decision.
     pub async fn add_sampling_request(
         &self,
-        request: SamplingRequest,
+        mut request: SamplingRequest,
     ) -> anyhow::Result<oneshot::Receiver<bool>> {
         let (tx, rx) = oneshot::channel();
+        let prompt_excerpt = sampling_audit::truncate_for_audit(&prompt_excerpt(&request));
+        self.sampling_audit
+            .record_created(&request.id, &request.extension_name, prompt_excerpt)
+            .await;
+
+        if let PolicyOutcome::Decided {
+            approved,
+            matched_rule,
+        } = self.sampling_policy.evaluate(&request)
+        {
+            tracing::info!(
+                "Sampling request for extension {} auto-{} by policy rule '{}'",
+                request.extension_name,
+                if approved { "approved" } else { "rejected" },
+                matched_rule
+            );
+            self.sampling_audit
+                .record_decision(
+                    &request.id,
+                    if approved {
+                        AuditDecision::AutoApproved
+                    } else {
+                        AuditDecision::AutoRejected
+                    },
+                    None,
+                    Some(matched_rule),
+                )
+                .await;
+            let _ = tx.send(approved);
+            return Ok(rx);
+        }
+
+        request.seq = self.sampling_seq.fetch_add(1, Ordering::SeqCst) + 1;
         let request_id = request.id.clone();
 
         // Store the request and response channel
         self.pending_sampling_requests
             .lock()
             .await
-            .insert(request_id.clone(), (request.clone(), tx));
+            .insert(request_id.clone(), (request.clone(), tx, Instant::now()));
+
+        // Keep the request around so a reconnecting SSE client can replay it via
+        // `Last-Event-ID` even after it scrolls out of the live broadcast channel.
+        {
+            let mut buffer = self.sampling_replay_buffer.lock().await;
+            if buffer.len() == SAMPLING_REPLAY_BUFFER_SIZE {
+                buffer.pop_front();
+            }
+            buffer.push_back(request.clone());
+        }
 
         // Broadcast the request to any listening SSE clients
         let _ = self.sampling_request_tx.send(request);
@@ -132,15 +328,81 @@ impl AppState {
         Ok(rx)
     }
 
-    /// Respond to a sampling request with approval or rejection
+    /// Wait for a pending sampling request to be decided, falling back to the configured
+    /// default decision if `sampling_config.timeout` elapses first. The timeout branch is
+    /// authoritative: it removes `request_id` from the pending map and records the expiry
+    /// itself (see `expire_sampling_request`) rather than leaving that to the background
+    /// reaper, so a decision can't land on an already-expired request.
+    pub async fn await_sampling_decision(
+        &self,
+        request_id: &str,
+        rx: oneshot::Receiver<bool>,
+    ) -> anyhow::Result<bool> {
+        tokio::select! {
+            result = rx => result.map_err(|_| anyhow::anyhow!("Approval channel closed")),
+            _ = tokio::time::sleep(self.sampling_config.timeout) => {
+                Ok(self.expire_sampling_request(request_id).await)
+            }
+        }
+    }
+
+    /// Sampling requests with `seq` greater than `last_seq`, in order, combining the replay
+    /// buffer with anything still pending approval. Used to catch up a reconnecting SSE client
+    /// that sent a `Last-Event-ID` header.
+    pub async fn sampling_requests_since(&self, last_seq: u64) -> Vec<SamplingRequest> {
+        let buffer = self.sampling_replay_buffer.lock().await;
+        let mut replay: Vec<SamplingRequest> = buffer
+            .iter()
+            .filter(|req| req.seq > last_seq)
+            .cloned()
+            .collect();
+        drop(buffer);
+
+        // The replay buffer is bounded and may have already evicted requests that are still
+        // awaiting a decision; make sure those aren't missed.
+        let pending = self.pending_sampling_requests.lock().await;
+        for (req, _, _) in pending.values() {
+            if req.seq > last_seq && !replay.iter().any(|r| r.id == req.id) {
+                replay.push(req.clone());
+            }
+        }
+        drop(pending);
+
+        replay.sort_by_key(|req| req.seq);
+        replay
+    }
+
+    /// Respond to a sampling request with approval or rejection. `decided_by` is the identity
+    /// established during the sampling auth handshake, recorded alongside the decision.
     pub async fn respond_to_sampling_request(
         &self,
         request_id: &str,
         approved: bool,
+        decided_by: Option<String>,
     ) -> anyhow::Result<()> {
         let mut requests = self.pending_sampling_requests.lock().await;
 
-        if let Some((_, tx)) = requests.remove(request_id) {
+        if let Some((request, tx, _)) = requests.remove(request_id) {
+            tracing::info!(
+                "Sampling request {} ({}) {} by {}",
+                request.id,
+                request.extension_name,
+                if approved { "approved" } else { "rejected" },
+                decided_by.as_deref().unwrap_or("unknown")
+            );
+            drop(requests);
+            self.sampling_audit
+                .record_decision(
+                    request_id,
+                    if approved {
+                        AuditDecision::Approved
+                    } else {
+                        AuditDecision::Rejected
+                    },
+                    decided_by,
+                    None,
+                )
+                .await;
             // Send the approval/rejection response
             let _ = tx.send(approved);
             Ok(())
@@ -158,7 +420,7 @@ impl AppState {
             .lock()
             .await
             .values()
-            .map(|(req, _)| req.clone())
+            .map(|(req, _, _)| req.clone())
             .collect()
     }
 
@@ -168,4 +430,74 @@ impl AppState {
     ) -> tokio::sync::broadcast::Receiver<SamplingRequest> {
         self.sampling_request_tx.subscribe()
     }
+
+    /// Subscribe to sampling lifecycle notifications (e.g. a request expiring)
+    pub fn subscribe_to_sampling_lifecycle(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<SamplingLifecycleEvent> {
+        self.sampling_lifecycle_tx.subscribe()
+    }
+
+    /// Re-read the sampling policy ruleset from `GOOSE_SAMPLING_POLICY_FILE` so operators can
+    /// update whitelists without restarting the server.
+    pub fn reload_sampling_policy(&self) -> anyhow::Result<()> {
+        self.sampling_policy.reload()
+    }
+
+    /// Paginated, filtered view of the sampling audit trail for `GET /sampling/history`.
+    pub async fn sampling_history(
+        &self,
+        filter: sampling_audit::AuditFilter,
+    ) -> (Vec<sampling_audit::AuditRecord>, usize) {
+        self.sampling_audit.history(filter).await
+    }
+
+    /// Record the provider's completion usage for a sampling request, once generation finishes.
+    pub async fn record_sampling_completion(
+        &self,
+        request_id: &str,
+        model: String,
+        input_tokens: Option<i64>,
+        output_tokens: Option<i64>,
+    ) {
+        self.sampling_audit
+            .record_completion(request_id, model, input_tokens, output_tokens)
+            .await;
+    }
+
+    /// Subscribe to incremental generation progress for a sampling request, creating its
+    /// broadcast channel on first subscription (e.g. the UI opens the progress stream before
+    /// the provider has emitted anything yet).
+    pub async fn subscribe_to_sampling_progress(
+        &self,
+        request_id: &str,
+    ) -> tokio::sync::broadcast::Receiver<SamplingProgressEvent> {
+        let mut channels = self.sampling_progress.lock().await;
+        channels
+            .entry(request_id.to_string())
+            .or_insert_with(|| tokio::sync::broadcast::channel(100).0)
+            .subscribe()
+    }
+
+    /// Publish an incremental progress update for a sampling request that's currently streaming.
+    pub async fn publish_sampling_progress(&self, request_id: &str, event: SamplingProgressEvent) {
+        let done = event.done;
+        let mut channels = self.sampling_progress.lock().await;
+        let tx = channels
+            .entry(request_id.to_string())
+            .or_insert_with(|| tokio::sync::broadcast::channel(100).0);
+        let _ = tx.send(event);
+        if done {
+            channels.remove(request_id);
+        }
+    }
+
+    /// Drop `request_id`'s progress channel, if one exists, closing out any subscribed SSE
+    /// stream. `publish_sampling_progress`'s `done` branch only runs for requests that actually
+    /// streamed; anything that resolves another way (rejected, expired, or a non-streaming
+    /// provider) must clean up through here instead, or the channel - and any client blocked
+    /// reading it - leaks forever.
+    pub async fn close_sampling_progress(&self, request_id: &str) {
+        self.sampling_progress.lock().await.remove(request_id);
+    }
 }